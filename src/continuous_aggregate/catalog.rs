@@ -0,0 +1,124 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! The `paradedb.continuous_aggregates` catalog table: one row per
+//! `WITH (continuous)` materialized view, recording how it buckets its
+//! source rows and how far it has been refreshed.
+
+use pgrx::prelude::*;
+
+extension_sql!(
+    r#"
+    CREATE TABLE paradedb.continuous_aggregates (
+        view_name regclass PRIMARY KEY,
+        view_query text NOT NULL,
+        bucket_function text NOT NULL,
+        bucket_width interval NOT NULL,
+        bucket_origin timestamp NULL,
+        bucket_timezone text NULL,
+        watermark timestamp NULL
+    );
+    "#,
+    name = "create_continuous_aggregates_catalog",
+);
+
+/// A single `paradedb.continuous_aggregates` row. `bucket_origin` and
+/// `bucket_timezone` are genuinely optional - not every bucketed view pins
+/// an explicit origin or timezone - so they're modeled as `Option`, not as
+/// sentinel empty strings that callers would have to remember to check for.
+pub struct ContinuousAggregate {
+    pub view_name: String,
+    /// The view's defining `SELECT`, re-run (restricted to the refreshed
+    /// bucket range) by `refresh_continuous_aggregate()` - distinct from
+    /// `bucket_function`, which only names which bucketing function the
+    /// view groups by.
+    pub view_query: String,
+    pub bucket_function: String,
+    pub bucket_width: Interval,
+    pub bucket_origin: Option<Timestamp>,
+    pub bucket_timezone: Option<String>,
+    pub watermark: Option<Timestamp>,
+}
+
+/// Registers a newly created `WITH (continuous)` materialized view in the
+/// catalog. Called from the `ProcessUtility` hook when it sees
+/// `CREATE MATERIALIZED VIEW ... WITH (continuous)`.
+pub fn register(aggregate: &ContinuousAggregate) -> Result<(), pgrx::spi::Error> {
+    Spi::connect(|mut client| {
+        client.update(
+            "INSERT INTO paradedb.continuous_aggregates
+                (view_name, view_query, bucket_function, bucket_width, bucket_origin, bucket_timezone, watermark)
+             VALUES ($1::regclass, $2, $3, $4, $5, $6, NULL)",
+            None,
+            &[
+                aggregate.view_name.clone().into(),
+                aggregate.view_query.clone().into(),
+                aggregate.bucket_function.clone().into(),
+                aggregate.bucket_width.into(),
+                aggregate.bucket_origin.into(),
+                aggregate.bucket_timezone.clone().into(),
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+/// Looks up a continuous aggregate's catalog row by its view name.
+///
+/// Every computed/cast column is given an explicit `AS` alias: a bare
+/// `col::type` cast over a column reference keeps the *column's* name in
+/// the result set (e.g. `view_name::text` comes back as `view_name`, not
+/// `"view_name::text"`), so indexing the SPI row by the literal cast
+/// expression text would never match.
+pub fn lookup(view_name: &str) -> Result<Option<ContinuousAggregate>, pgrx::spi::Error> {
+    Spi::connect(|client| {
+        let row = client.select(
+            "SELECT view_name::text AS view_name, view_query, bucket_function, bucket_width,
+                    bucket_origin, bucket_timezone, watermark
+             FROM paradedb.continuous_aggregates
+             WHERE view_name = $1::regclass",
+            Some(1),
+            &[view_name.into()],
+        )?;
+
+        row.first()
+            .map(|row| {
+                Ok(ContinuousAggregate {
+                    view_name: row["view_name"].value::<String>()?.unwrap(),
+                    view_query: row["view_query"].value::<String>()?.unwrap(),
+                    bucket_function: row["bucket_function"].value::<String>()?.unwrap(),
+                    bucket_width: row["bucket_width"].value::<Interval>()?.unwrap(),
+                    bucket_origin: row["bucket_origin"].value::<Timestamp>()?,
+                    bucket_timezone: row["bucket_timezone"].value::<String>()?,
+                    watermark: row["watermark"].value::<Timestamp>()?,
+                })
+            })
+            .transpose()
+    })
+}
+
+/// Advances a continuous aggregate's watermark after a successful refresh.
+pub fn set_watermark(view_name: &str, watermark: Timestamp) -> Result<(), pgrx::spi::Error> {
+    Spi::connect(|mut client| {
+        client.update(
+            "UPDATE paradedb.continuous_aggregates SET watermark = $2 WHERE view_name = $1::regclass",
+            None,
+            &[view_name.into(), watermark.into()],
+        )?;
+        Ok(())
+    })
+}
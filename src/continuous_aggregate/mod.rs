@@ -0,0 +1,39 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Incrementally-refreshable rollups over DuckDB foreign tables, e.g.:
+//!
+//! ```sql
+//! CREATE MATERIALIZED VIEW m WITH (continuous) AS
+//!     SELECT time_bucket(INTERVAL '1 hour', ts) AS bucket, avg(value)
+//!     FROM timeseries
+//!     GROUP BY bucket;
+//! ```
+//!
+//! `WITH (continuous)` materialized views are tracked in [`catalog`] and
+//! refreshed on demand via [`refresh::refresh_continuous_aggregate`] rather
+//! than by a full `REFRESH MATERIALIZED VIEW` rebuild. [`parse`] pulls the
+//! catalog row's fields out of the view's `CREATE MATERIALIZED VIEW`
+//! statement; `hooks::process_utility` is what actually calls into it.
+
+mod catalog;
+mod parse;
+mod refresh;
+
+pub use catalog::*;
+pub use parse::*;
+pub use refresh::*;
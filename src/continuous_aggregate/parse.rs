@@ -0,0 +1,271 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Pulls the pieces `continuous_aggregate::catalog` needs - the defining
+//! `SELECT`, which bucketing function it groups by, and that function's
+//! width/origin/timezone arguments - out of the raw text of a
+//! `CREATE MATERIALIZED VIEW ... WITH (continuous) AS SELECT ...`
+//! statement.
+//!
+//! This is deliberately a text scan rather than a full parse-tree walk:
+//! the `ProcessUtility` hook already has the statement's `objtype` and
+//! `WITH` options off the parse tree (see `hooks::process_utility`), but
+//! digging the bucketing call's *arguments* back out of the target list
+//! would mean re-deparsing arbitrary expression nodes. Scanning the
+//! original query text for the first `time_bucket(...)` call is far
+//! simpler and covers the queries these views are expected to look like.
+//!
+//! `time_bucket_gapfill(...)` is deliberately not in [`BUCKET_FUNCTIONS`]:
+//! its argument list (`bucket_width, source_query, start, finish, fill`)
+//! doesn't share `time_bucket`'s (`width, ts, origin, timezone`) shape, so
+//! parsing it the same way would misread `source_query`'s string literal as
+//! a positional `origin` expression. A continuous aggregate defined over
+//! `time_bucket_gapfill(...)` won't be recognized until the two designs are
+//! reconciled.
+
+/// The pieces of a continuous aggregate's definition needed to populate its
+/// `paradedb.continuous_aggregates` row.
+pub struct ViewDefinition {
+    pub view_query: String,
+    pub bucket_function: String,
+    pub bucket_width_expr: String,
+    pub origin_expr: Option<String>,
+    pub timezone_expr: Option<String>,
+}
+
+const BUCKET_FUNCTIONS: &[&str] = &["time_bucket"];
+
+/// Parses a full `CREATE MATERIALIZED VIEW ... AS SELECT ...` statement.
+/// Returns `None` if it can't find a top-level `AS` (malformed input) or a
+/// recognized bucketing function call in the defining query.
+pub fn parse_view_definition(create_sql: &str) -> Option<ViewDefinition> {
+    let as_pos = find_top_level_as(create_sql)?;
+    let view_query = create_sql[as_pos..]
+        .trim()
+        .trim_end_matches(';')
+        .trim()
+        .to_string();
+
+    let (bucket_function, args) = find_bucket_call(&view_query)?;
+    let args = split_top_level_args(&args);
+    let bucket_width_expr = args.first()?.trim().to_string();
+
+    let mut origin_expr = None;
+    let mut timezone_expr = None;
+    for arg in args.iter().skip(2) {
+        let arg = arg.trim();
+        if let Some(value) = strip_named_arg(arg, "origin") {
+            origin_expr = Some(value.to_string());
+        } else if let Some(value) = strip_named_arg(arg, "timezone") {
+            timezone_expr = Some(value.to_string());
+        } else if origin_expr.is_none() {
+            // The common 3-argument form (`time_bucket(width, ts, origin)`)
+            // has no `=>` label at all.
+            origin_expr = Some(arg.to_string());
+        }
+    }
+
+    Some(ViewDefinition {
+        view_query,
+        bucket_function,
+        bucket_width_expr,
+        origin_expr,
+        timezone_expr,
+    })
+}
+
+/// Finds the `AS` that introduces the defining query, i.e. the first `AS`
+/// appearing outside of any parentheses (so it skips past e.g.
+/// `WITH (continuous)`), and returns the index right after it.
+fn find_top_level_as(sql: &str) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let lower = sql.to_ascii_lowercase();
+    let lower_bytes = lower.as_bytes();
+    let mut depth: i32 = 0;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0
+            && lower_bytes[i..].starts_with(b"as")
+            && i > 0
+            && bytes[i - 1].is_ascii_whitespace()
+            && bytes.get(i + 2).map(|c| c.is_ascii_whitespace()).unwrap_or(false)
+        {
+            return Some(i + 2);
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Finds the first call to one of [`BUCKET_FUNCTIONS`] in `query`, case
+/// insensitively, returning its canonical name and raw (unsplit) argument
+/// text.
+fn find_bucket_call(query: &str) -> Option<(String, String)> {
+    let lower = query.to_ascii_lowercase();
+
+    for name in BUCKET_FUNCTIONS {
+        let needle = format!("{name}(");
+        if let Some(start) = lower.find(&needle) {
+            let args_start = start + needle.len();
+            let args_end = matching_paren(query, args_start - 1)?;
+            return Some((name.to_string(), query[args_start..args_end].to_string()));
+        }
+    }
+
+    None
+}
+
+/// Given the index of an opening `(`, returns the index of its matching `)`.
+fn matching_paren(s: &str, open_paren: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    for (i, &b) in bytes.iter().enumerate().skip(open_paren) {
+        match b {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `args` on top-level commas (i.e. not nested inside parentheses).
+fn split_top_level_args(args: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+
+    for c in args.chars() {
+        match c {
+            '(' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+
+    parts
+}
+
+/// Best-effort extraction of the first relation name following the view
+/// query's `FROM` keyword. Used only to sanity-check that a continuous
+/// aggregate's source looks like a DuckDB-backed relation (see
+/// `hooks::validate_continuous_aggregate_source`) - not a general SQL
+/// parser: it returns whatever token follows `FROM`, so for `FROM a, b`
+/// only `a` is returned, and a subquery yields its opening token rather
+/// than a relation name.
+///
+/// Tokenizes on whitespace runs rather than matching a literal `" from "`
+/// substring, so flush-left SQL (`SELECT x\nFROM t`) is found just as
+/// reliably as SQL with a space before `FROM`.
+pub fn first_from_relation(view_query: &str) -> Option<String> {
+    let mut tokens = view_query.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token.eq_ignore_ascii_case("from") {
+            let relation = tokens.next()?.trim_end_matches(',');
+            return (!relation.is_empty()).then(|| relation.to_string());
+        }
+    }
+    None
+}
+
+/// If `arg` is of the form `name => value`, returns `value` trimmed.
+fn strip_named_arg<'a>(arg: &'a str, name: &str) -> Option<&'a str> {
+    let lower = arg.to_ascii_lowercase();
+    let needle = format!("{name}=>");
+    let normalized: String = lower.chars().filter(|c| !c.is_whitespace()).collect();
+    if normalized.starts_with(&needle) {
+        let value_start = arg.find("=>")? + 2;
+        Some(arg[value_start..].trim())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_origin_and_timezone() {
+        let sql = "CREATE MATERIALIZED VIEW m WITH (continuous) AS SELECT time_bucket(INTERVAL '1 hour', ts, origin => TIMESTAMP '2000-01-01', timezone => 'UTC') AS bucket, avg(value) FROM timeseries GROUP BY bucket;";
+        let def = parse_view_definition(sql).unwrap();
+
+        assert_eq!(def.bucket_function, "time_bucket");
+        assert_eq!(def.bucket_width_expr, "INTERVAL '1 hour'");
+        assert_eq!(def.origin_expr.as_deref(), Some("TIMESTAMP '2000-01-01'"));
+        assert_eq!(def.timezone_expr.as_deref(), Some("'UTC'"));
+        assert!(def.view_query.starts_with("SELECT time_bucket"));
+    }
+
+    #[test]
+    fn parses_positional_origin() {
+        let sql = "CREATE MATERIALIZED VIEW m WITH (continuous) AS SELECT time_bucket(INTERVAL '1 day', ts, DATE '1980-01-01') AS bucket, avg(value) FROM t GROUP BY bucket;";
+        let def = parse_view_definition(sql).unwrap();
+
+        assert_eq!(def.origin_expr.as_deref(), Some("DATE '1980-01-01'"));
+        assert_eq!(def.timezone_expr, None);
+    }
+
+    #[test]
+    fn returns_none_without_a_bucket_call() {
+        let sql = "CREATE MATERIALIZED VIEW m WITH (continuous) AS SELECT * FROM t;";
+        assert!(parse_view_definition(sql).is_none());
+    }
+
+    #[test]
+    fn extracts_first_from_relation() {
+        let query = "SELECT time_bucket(INTERVAL '1 hour', ts) AS bucket, avg(value) FROM timeseries GROUP BY bucket";
+        assert_eq!(first_from_relation(query).as_deref(), Some("timeseries"));
+    }
+
+    #[test]
+    fn first_from_relation_is_none_without_a_from_clause() {
+        assert_eq!(first_from_relation("SELECT 1"), None);
+    }
+
+    #[test]
+    fn extracts_first_from_relation_with_no_space_before_from() {
+        let query = "SELECT time_bucket(INTERVAL '1 hour', ts) AS bucket, avg(value)\nFROM timeseries\nGROUP BY bucket";
+        assert_eq!(first_from_relation(query).as_deref(), Some("timeseries"));
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Proleptic Gregorian calendar math for the native (non-DuckDB)
+//! `time_bucket()` fallback. Kept dependency-free (no `chrono`) since it
+//! only needs to go back and forth between a day count and a
+//! year/month/day triple.
+
+/// Days between the Postgres epoch (2000-01-01) and the Unix epoch
+/// (1970-01-01), i.e. `days_from_civil(2000, 1, 1)`.
+const PG_EPOCH_UNIX_DAYS: i64 = 10_957;
+
+/// Howard Hinnant's `civil_from_days`: converts a day count since
+/// 1970-01-01 into a proleptic-Gregorian (year, month, day) triple.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+/// The inverse of [`civil_from_days`].
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = ((m as i64 + 9) % 12) as i64;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Number of days in the given proleptic-Gregorian month.
+fn days_in_month(y: i64, m: u32) -> u32 {
+    const DAYS: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if m == 2 && (y % 4 == 0 && (y % 100 != 0 || y % 400 == 0)) {
+        29
+    } else {
+        DAYS[(m - 1) as usize]
+    }
+}
+
+/// A calendar date split into its year/month/day, relative to the Postgres
+/// epoch.
+#[derive(Clone, Copy)]
+pub struct CivilDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl CivilDate {
+    /// `pg_days` is a day count relative to the Postgres epoch (2000-01-01),
+    /// i.e. the internal representation of Postgres' `date` type.
+    pub fn from_pg_days(pg_days: i64) -> Self {
+        let (year, month, day) = civil_from_days(pg_days + PG_EPOCH_UNIX_DAYS);
+        CivilDate { year, month, day }
+    }
+
+    pub fn to_pg_days(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - PG_EPOCH_UNIX_DAYS
+    }
+
+    /// This date's ordinal month count, i.e. `year * 12 + (month - 1)`,
+    /// used to measure whole-month distances between two dates.
+    pub fn total_months(self) -> i64 {
+        self.year * 12 + (self.month as i64 - 1)
+    }
+
+    /// Returns the date `months` whole months after this one, clamping the
+    /// day of month so e.g. January 31st plus one month is February 28th
+    /// (or 29th) rather than overflowing into March.
+    pub fn add_months(self, months: i64) -> Self {
+        let total = self.total_months() + months;
+        let year = total.div_euclid(12);
+        let month = (total.rem_euclid(12) + 1) as u32;
+        let day = self.day.min(days_in_month(year, month));
+        CivilDate { year, month, day }
+    }
+}
+
+/// Buckets a fixed-width (non-calendar) interval expressed entirely in
+/// microseconds: `origin + floor((instant - origin) / width) * width`.
+pub fn bucket_fixed_width(width_micros: i64, instant_micros: i64, origin_micros: i64) -> i64 {
+    assert!(width_micros > 0, "time_bucket width must be positive");
+    origin_micros + (instant_micros - origin_micros).div_euclid(width_micros) * width_micros
+}
+
+/// Buckets a calendar-width (month/year) interval by counting whole months
+/// from `origin` to `instant`, flooring to a multiple of `width_months`,
+/// and adding that many months back onto `origin`'s calendar date.
+pub fn bucket_calendar_width(
+    width_months: i64,
+    instant: CivilDate,
+    instant_time_micros: i64,
+    origin: CivilDate,
+    origin_time_micros: i64,
+) -> CivilDate {
+    assert!(width_months > 0, "time_bucket width must be positive");
+
+    let mut months_between = instant.total_months() - origin.total_months();
+    if (instant.day, instant_time_micros) < (origin.day, origin_time_micros) {
+        months_between -= 1;
+    }
+
+    let bucketed_months = months_between.div_euclid(width_months) * width_months;
+    origin.add_months(bucketed_months)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_pg_epoch() {
+        let date = CivilDate::from_pg_days(0);
+        assert_eq!((date.year, date.month, date.day), (2000, 1, 1));
+        assert_eq!(date.to_pg_days(), 0);
+    }
+
+    #[test]
+    fn add_months_clamps_day_of_month() {
+        let jan_31_2000 = CivilDate::from_pg_days(30);
+        let date = jan_31_2000.add_months(1);
+        assert_eq!((date.year, date.month, date.day), (2000, 2, 29));
+    }
+
+    #[test]
+    fn bucket_fixed_width_floors_toward_origin() {
+        const MINUTE: i64 = 60_000_000;
+        assert_eq!(bucket_fixed_width(MINUTE, 90_000_000, 0), 60_000_000);
+        assert_eq!(bucket_fixed_width(MINUTE, -1, 0), -MINUTE);
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! The single integration point between pg_analytics' Rust code and the
+//! embedded DuckDB connection the FDW scan executor drives.
+//!
+//! This tree slice doesn't carry that scan executor (see the
+//! [`crate::duckdb::scan`] module docs), so `execute_on_duckdb` only logs
+//! the statement it would run; a
+//! full build wires it to the `duckdb-rs` connection the scan executor
+//! already holds open for the backend.
+
+use pgrx::prelude::*;
+
+/// Runs `sql` against the embedded DuckDB connection, e.g. a generated
+/// `CREATE OR REPLACE SECRET ...` statement. Called from the
+/// `ProcessUtility` hook when a `CREATE SERVER`/`CREATE FOREIGN TABLE`'s
+/// options require DuckDB-side setup before the relation can be scanned.
+pub fn execute_on_duckdb(sql: &str) {
+    debug1!("pg_analytics: executing on DuckDB connection: {sql}");
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! `infinity`/`-infinity` handling for timestamp-like columns coming out of
+//! Arrow record batches (Parquet, Iceberg, Delta, ...) on their way to a
+//! Postgres datum.
+//!
+//! DuckDB and Arrow represent `infinity`/`-infinity` the same way Postgres
+//! does internally: the min/max value the underlying integer type can hold,
+//! rather than some particular far-future instant. That representation
+//! happens to need no epoch conversion at all (shifting `i64::MAX`
+//! microseconds from the Unix epoch to the Postgres epoch would turn it into
+//! a finite, wrong timestamp, and shifting `i64::MIN` would overflow), so
+//! the sentinel must
+//! be detected and passed through *before* applying the usual epoch-shift
+//! arithmetic used to convert an Arrow value to a Postgres one.
+//!
+//! [`crate::duckdb::scan`] calls these per row of an Arrow array; see its
+//! module docs for why nothing in this crate reaches it during a scan.
+
+/// Postgres' internal encoding of `timestamp`/`timestamptz` `infinity` and
+/// `-infinity` (`timestamp.h`'s `DT_NOEND`/`DT_NOBEGIN`). Arrow's own
+/// microsecond sentinels use the same values, so no translation is needed
+/// beyond recognizing them.
+const PG_TIMESTAMP_INFINITY: i64 = i64::MAX;
+const PG_TIMESTAMP_NEG_INFINITY: i64 = i64::MIN;
+
+/// Postgres' internal encoding of `date` `infinity`/`-infinity`
+/// (`date.h`'s `DATEVAL_NOEND`/`DATEVAL_NOBEGIN`).
+const PG_DATE_INFINITY: i32 = i32::MAX;
+const PG_DATE_NEG_INFINITY: i32 = i32::MIN;
+
+/// Microseconds between the Unix epoch (1970-01-01, which Arrow/Parquet
+/// timestamps are relative to) and the Postgres epoch (2000-01-01).
+const UNIX_TO_PG_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+/// Days between the Unix epoch and the Postgres epoch.
+const UNIX_TO_PG_EPOCH_DAYS: i32 = 10_957;
+
+/// Converts a `timestamp`/`timestamptz` column value read out of an Arrow
+/// record batch (microseconds since the Unix epoch) into a Postgres
+/// `Timestamp`/`TimestampWithTimeZone` datum (microseconds since the
+/// Postgres epoch), passing `infinity`/`-infinity` through unchanged.
+pub fn arrow_timestamp_micros_to_pg(unix_micros: i64) -> i64 {
+    match unix_micros {
+        PG_TIMESTAMP_INFINITY => PG_TIMESTAMP_INFINITY,
+        PG_TIMESTAMP_NEG_INFINITY => PG_TIMESTAMP_NEG_INFINITY,
+        finite => finite - UNIX_TO_PG_EPOCH_MICROS,
+    }
+}
+
+/// Converts a `date` column value read out of an Arrow record batch (days
+/// since the Unix epoch) into a Postgres `Date` datum (days since the
+/// Postgres epoch), passing `infinity`/`-infinity` through unchanged.
+pub fn arrow_date32_days_to_pg(unix_days: i32) -> i32 {
+    match unix_days {
+        PG_DATE_INFINITY => PG_DATE_INFINITY,
+        PG_DATE_NEG_INFINITY => PG_DATE_NEG_INFINITY,
+        finite => finite - UNIX_TO_PG_EPOCH_DAYS,
+    }
+}
+
+/// True if a Postgres-epoch timestamp/timestamptz microsecond value is the
+/// `infinity` or `-infinity` sentinel. `time_bucket()` (native and
+/// DuckDB-pushdown alike) must check this before doing bucket arithmetic:
+/// an infinite instant buckets to itself, since `origin + floor((infinity -
+/// origin) / width) * width` would otherwise overflow.
+pub fn is_timestamp_infinite(pg_micros: i64) -> bool {
+    matches!(pg_micros, PG_TIMESTAMP_INFINITY | PG_TIMESTAMP_NEG_INFINITY)
+}
+
+/// True if a Postgres-epoch `date` day value is the `infinity` or
+/// `-infinity` sentinel. See [`is_timestamp_infinite`].
+pub fn is_date_infinite(pg_days: i32) -> bool {
+    matches!(pg_days, PG_DATE_INFINITY | PG_DATE_NEG_INFINITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_through_infinite_timestamps() {
+        assert_eq!(
+            arrow_timestamp_micros_to_pg(PG_TIMESTAMP_INFINITY),
+            PG_TIMESTAMP_INFINITY
+        );
+        assert_eq!(
+            arrow_timestamp_micros_to_pg(PG_TIMESTAMP_NEG_INFINITY),
+            PG_TIMESTAMP_NEG_INFINITY
+        );
+    }
+
+    #[test]
+    fn shifts_finite_timestamps_by_epoch_delta() {
+        assert_eq!(arrow_timestamp_micros_to_pg(UNIX_TO_PG_EPOCH_MICROS), 0);
+    }
+
+    #[test]
+    fn passes_through_infinite_dates() {
+        assert_eq!(arrow_date32_days_to_pg(PG_DATE_INFINITY), PG_DATE_INFINITY);
+        assert_eq!(
+            arrow_date32_days_to_pg(PG_DATE_NEG_INFINITY),
+            PG_DATE_NEG_INFINITY
+        );
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! `time_bucket_gapfill()` and its companion marker functions `locf()` and
+//! `interpolate()`.
+//!
+//! Unlike plain `time_bucket()`, which only ever emits a bucket for rows
+//! that exist, `time_bucket_gapfill()` emits a row for every bucket in
+//! `[start, finish)`, whether or not the source has data there. Filling
+//! those gaps the way TimescaleDB does - as an ordered pass a query's own
+//! planner rewrite splices in around arbitrary grouped aggregates - would
+//! need a planner hook this crate doesn't have; instead,
+//! `time_bucket_gapfill()` here is a self-contained SPI table function that
+//! takes the source query as an argument:
+//!
+//! 1. Runs `source_query` via SPI, which must return exactly three columns:
+//!    a text partition key, a `timestamp` bucket and a `double precision`
+//!    value (see below).
+//! 2. Generates the dense bucket series for `[start, finish)` at
+//!    `bucket_width` ([`dense_bucket_series`]) and left-joins the query's
+//!    rows onto it by bucket value, so missing buckets come through with a
+//!    `NULL` value.
+//! 3. Runs the requested fill pass - `locf` ([`locf_fill`]), `interpolate`
+//!    ([`linear_interpolate_fill`]), or `none` - over the dense series in
+//!    bucket order.
+//!
+//! `locf()` and `interpolate()` remain as marker functions for the SQL
+//! surface `SELECT locf(value) FROM ...` suggests: outside of a
+//! `time_bucket_gapfill()` call there's no row ordering to fill along, so
+//! calling them directly just raises [`MARKER_FUNCTION_ERROR`]. Pass
+//! `fill => 'locf'`/`fill => 'interpolate'` to `time_bucket_gapfill()`
+//! itself to actually fill gaps.
+//!
+//! `source_query` is free to `GROUP BY` a partition key (e.g. `sensor_id`)
+//! alongside the bucket; `time_bucket_gapfill()` runs the dense/fill pass
+//! independently per partition, so gaps in one partition's series are never
+//! filled from another's rows. `source_query` must select exactly three
+//! columns, in order: the partition key (read as text), the `timestamp`
+//! bucket, and the `double precision` value. A single-series query with no
+//! real grouping can pass a constant text value (e.g.
+//! `SELECT '0', ts, value FROM ...`) as the partition key.
+
+use pgrx::prelude::*;
+use std::collections::BTreeMap;
+
+pub const MARKER_FUNCTION_ERROR: &str = "locf() and interpolate() can only be used in the SELECT list of a query that groups by time_bucket_gapfill()";
+
+/// Emits one `(partition, bucket, value)` row per bucket in `[start,
+/// finish)` at `bucket_width` spacing, per distinct partition key returned
+/// by `source_query` (which must select a partition key, a `timestamp`
+/// bucket column, and a `double precision` value column, in that order),
+/// filling any bucket a partition has no row for according to `fill`:
+/// `'locf'` (the default), `'interpolate'`, or `'none'` (leave it `NULL`).
+/// See the module docs for how to call this for a single, ungrouped series.
+#[pg_extern]
+fn time_bucket_gapfill(
+    bucket_width: Interval,
+    source_query: &str,
+    start: Timestamp,
+    finish: Timestamp,
+    fill: default!(String, "'locf'"),
+) -> Result<
+    TableIterator<
+        'static,
+        (
+            name!(partition, String),
+            name!(bucket, Timestamp),
+            name!(value, Option<f64>),
+        ),
+    >,
+    pgrx::spi::Error,
+> {
+    let width_micros = interval_micros(bucket_width);
+    let start_micros: i64 = start.into();
+    let finish_micros: i64 = finish.into();
+
+    let buckets = dense_bucket_series(start_micros, finish_micros, width_micros);
+
+    // Partitions in partition-key order, each holding its own dense series.
+    let mut partitions: BTreeMap<String, Vec<Option<f64>>> = BTreeMap::new();
+
+    Spi::connect(|client| {
+        let rows = client.select(source_query, None, &[])?;
+        for row in rows {
+            let Some(partition) = row.get::<String>(1)? else {
+                continue;
+            };
+            let Some(bucket) = row.get::<Timestamp>(2)? else {
+                continue;
+            };
+            let value = row.get::<f64>(3)?;
+
+            let bucket_micros: i64 = bucket.into();
+            if let Ok(index) = buckets.binary_search(&bucket_micros) {
+                let values = partitions
+                    .entry(partition)
+                    .or_insert_with(|| vec![None; buckets.len()]);
+                values[index] = value;
+            }
+        }
+        Ok::<(), pgrx::spi::Error>(())
+    })?;
+
+    let mut rows = Vec::new();
+    for (partition, mut values) in partitions {
+        match fill.as_str() {
+            "locf" => locf_fill(&mut values),
+            "interpolate" => linear_interpolate_fill(&mut values),
+            "none" => {}
+            other => error!("time_bucket_gapfill(): unknown fill \"{other}\", expected 'locf', 'interpolate', or 'none'"),
+        }
+
+        rows.extend(
+            buckets
+                .iter()
+                .zip(values)
+                .map(|(&bucket, value)| (partition.clone(), Timestamp::from(bucket), value)),
+        );
+    }
+
+    Ok(TableIterator::new(rows))
+}
+
+/// Splits an `Interval` into a single microsecond width, same calendar
+/// caveat as `time_bucket()` itself: a gapfill width is expected to be a
+/// fixed duration (e.g. `'1 MINUTE'`), not a calendar month/year component.
+fn interval_micros(width: Interval) -> i64 {
+    const USECS_PER_DAY: i64 = 86_400_000_000;
+    assert!(
+        width.months() == 0,
+        "time_bucket_gapfill() width must be a fixed duration, not a calendar month/year interval"
+    );
+    width.days() as i64 * USECS_PER_DAY + width.micros()
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn locf(value: AnyElement) -> AnyElement {
+    let _ = value;
+    error!("{MARKER_FUNCTION_ERROR}")
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn interpolate(value: AnyElement) -> AnyElement {
+    let _ = value;
+    error!("{MARKER_FUNCTION_ERROR}")
+}
+
+/// Every bucket in `[start, finish)` at `width_micros` spacing. This is the
+/// dense series `time_bucket_gapfill()` left-joins its source query's rows
+/// onto by bucket value.
+pub fn dense_bucket_series(start_micros: i64, finish_micros: i64, width_micros: i64) -> Vec<i64> {
+    assert!(width_micros > 0, "time_bucket_gapfill width must be positive");
+
+    let mut buckets = Vec::new();
+    let mut bucket = start_micros;
+    while bucket < finish_micros {
+        buckets.push(bucket);
+        bucket += width_micros;
+    }
+    buckets
+}
+
+/// Last-observation-carried-forward: replaces each `None` with the nearest
+/// preceding `Some`, in bucket order. Leading gaps (no preceding value)
+/// stay `None`.
+pub fn locf_fill<T: Copy>(values: &mut [Option<T>]) {
+    let mut last = None;
+    for value in values.iter_mut() {
+        match value {
+            Some(v) => last = Some(*v),
+            None => *value = last,
+        }
+    }
+}
+
+/// Linear interpolation: replaces each `None` with a value linearly
+/// weighted between the nearest preceding and following `Some`, by bucket
+/// distance. Gaps with no value on one side (leading/trailing) stay `None`;
+/// unlike `locf`, which fills a trailing gap.
+pub fn linear_interpolate_fill(values: &mut [Option<f64>]) {
+    let n = values.len();
+    let mut i = 0;
+    while i < n {
+        if values[i].is_some() {
+            i += 1;
+            continue;
+        }
+
+        let gap_start = i;
+        while i < n && values[i].is_none() {
+            i += 1;
+        }
+        let gap_end = i;
+
+        let Some(before) = (gap_start > 0).then(|| values[gap_start - 1]).flatten() else {
+            continue;
+        };
+        let Some(after) = (gap_end < n).then(|| values[gap_end]).flatten() else {
+            continue;
+        };
+
+        let span = (gap_end - gap_start + 1) as f64;
+        for (offset, slot) in values[gap_start..gap_end].iter_mut().enumerate() {
+            let weight = (offset + 1) as f64 / span;
+            *slot = Some(before + (after - before) * weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_bucket_series_covers_half_open_range() {
+        assert_eq!(dense_bucket_series(0, 300, 100), vec![0, 100, 200]);
+    }
+
+    #[test]
+    fn locf_fill_carries_last_value_forward() {
+        let mut values = vec![None, Some(1), None, None, Some(4)];
+        locf_fill(&mut values);
+        assert_eq!(values, vec![None, Some(1), Some(1), Some(1), Some(4)]);
+    }
+
+    #[test]
+    fn linear_interpolate_fill_weights_by_distance() {
+        let mut values = vec![Some(0.0), None, None, Some(3.0)];
+        linear_interpolate_fill(&mut values);
+        assert_eq!(values, vec![Some(0.0), Some(1.0), Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn linear_interpolate_fill_leaves_unbounded_gaps_alone() {
+        let mut values: Vec<Option<f64>> = vec![None, Some(1.0)];
+        linear_interpolate_fill(&mut values);
+        assert_eq!(values, vec![None, Some(1.0)]);
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Converts one row of an Arrow-native column (as handed back by DuckDB for
+//! a foreign-table scan) into the Postgres datum the executor expects.
+//!
+//! This tree slice doesn't carry the FDW scan executor itself (there's no
+//! `FdwRoutine` registration anywhere in this crate to call from), so
+//! `timestamp_column_value`/`date_column_value` aren't reachable from
+//! anything but their own unit tests below. A full build's scan executor
+//! would read a `TimestampMicrosecondArray`/`Date32Array` out of each
+//! record batch DuckDB produces and call these per row while building the
+//! output tuple; see [`crate::duckdb::connection`] for the same caveat on
+//! the DuckDB connection side.
+
+use crate::duckdb::datum::{arrow_date32_days_to_pg, arrow_timestamp_micros_to_pg};
+use arrow_array::{Date32Array, TimestampMicrosecondArray};
+
+/// Reads row `index` out of a `timestamp`/`timestamptz` Arrow array and
+/// returns the Postgres-epoch microsecond value for it, or `None` if the
+/// value is Arrow-null (which becomes a SQL `NULL`, not `-infinity`).
+pub fn timestamp_column_value(array: &TimestampMicrosecondArray, index: usize) -> Option<i64> {
+    if array.is_null(index) {
+        return None;
+    }
+    Some(arrow_timestamp_micros_to_pg(array.value(index)))
+}
+
+/// Reads row `index` out of a `date` Arrow array and returns the
+/// Postgres-epoch day value for it, or `None` if the value is Arrow-null.
+pub fn date_column_value(array: &Date32Array, index: usize) -> Option<i32> {
+    if array.is_null(index) {
+        return None;
+    }
+    Some(arrow_date32_days_to_pg(array.value(index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_finite_timestamp_row() {
+        let array = TimestampMicrosecondArray::from(vec![946_684_800_000_000]);
+        assert_eq!(timestamp_column_value(&array, 0), Some(0));
+    }
+
+    #[test]
+    fn passes_through_infinite_timestamp_row() {
+        let array = TimestampMicrosecondArray::from(vec![i64::MAX, i64::MIN]);
+        assert_eq!(timestamp_column_value(&array, 0), Some(i64::MAX));
+        assert_eq!(timestamp_column_value(&array, 1), Some(i64::MIN));
+    }
+
+    #[test]
+    fn null_timestamp_row_stays_null() {
+        let array = TimestampMicrosecondArray::from(vec![Some(0i64), None]);
+        assert_eq!(timestamp_column_value(&array, 1), None);
+    }
+
+    #[test]
+    fn converts_finite_date_row() {
+        let array = Date32Array::from(vec![10_957]);
+        assert_eq!(date_column_value(&array, 0), Some(0));
+    }
+
+    #[test]
+    fn passes_through_infinite_date_row() {
+        let array = Date32Array::from(vec![i32::MAX, i32::MIN]);
+        assert_eq!(date_column_value(&array, 0), Some(i32::MAX));
+        assert_eq!(date_column_value(&array, 1), Some(i32::MIN));
+    }
+
+    #[test]
+    fn null_date_row_stays_null() {
+        let array = Date32Array::from(vec![Some(0i32), None]);
+        assert_eq!(date_column_value(&array, 1), None);
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! `time_bucket()` is declared here so Postgres will parse and plan it. When
+//! the query is run entirely against DuckDB foreign tables, the whole query
+//! is pushed down and executed by DuckDB, whose own `time_bucket()`
+//! understands the `origin` and `timezone` arguments natively - these Rust
+//! bodies never run in that path. Otherwise (e.g. a plain heap table, or a
+//! join that mixes a heap table with a foreign one) pg_analytics falls back
+//! to running the query itself, and these bodies compute the bucket
+//! natively using [`calendar`].
+//!
+//! The timezone-aware overloads have no native fallback yet - they only
+//! work under DuckDB pushdown - since correct timezone bucketing needs a
+//! tzdata lookup this crate doesn't otherwise carry.
+
+use crate::duckdb::calendar::{bucket_calendar_width, bucket_fixed_width, CivilDate};
+use crate::duckdb::datum::{is_date_infinite, is_timestamp_infinite};
+use pgrx::prelude::*;
+
+/// Raised when a timezone-aware `time_bucket()` overload is evaluated by the
+/// Postgres executor instead of being pushed down to DuckDB, i.e. the query
+/// touches at least one relation that isn't a DuckDB foreign table.
+pub const FALLBACK_ERROR: &str = "Function `time_bucket()` must be used with a DuckDB FDW. Native postgres does not support this function.If you believe this function should be implemented natively as a fallback please submit a ticket to https://github.com/paradedb/pg_analytics/issues.";
+
+const USECS_PER_DAY: i64 = 86_400_000_000;
+
+/// Splits an `Interval` into a calendar-months component and a fixed
+/// microseconds component. `time_bucket()` widths are one or the other in
+/// practice (e.g. `'1 MONTH'` or `'6 MINUTE'`, never both), so callers pick
+/// whichever is non-zero.
+fn width_components(bucket_width: Interval) -> (i64, i64) {
+    let months = bucket_width.months() as i64;
+    let micros = bucket_width.days() as i64 * USECS_PER_DAY + bucket_width.micros();
+    (months, micros)
+}
+
+fn bucket_timestamp(bucket_width: Interval, ts: Timestamp, origin: Timestamp) -> Timestamp {
+    let ts_micros: i64 = ts.into();
+    let origin_micros: i64 = origin.into();
+
+    // `infinity`/`-infinity` bucket to themselves rather than through the
+    // usual arithmetic, which would overflow.
+    if is_timestamp_infinite(ts_micros) {
+        return Timestamp::from(ts_micros);
+    }
+
+    let (width_months, width_micros) = width_components(bucket_width);
+
+    if width_months != 0 {
+        let ts_day_micros = ts_micros.rem_euclid(USECS_PER_DAY);
+        let origin_day_micros = origin_micros.rem_euclid(USECS_PER_DAY);
+        let ts_date = CivilDate::from_pg_days(ts_micros.div_euclid(USECS_PER_DAY));
+        let origin_date = CivilDate::from_pg_days(origin_micros.div_euclid(USECS_PER_DAY));
+
+        let bucket_date =
+            bucket_calendar_width(width_months, ts_date, ts_day_micros, origin_date, origin_day_micros);
+
+        Timestamp::from(bucket_date.to_pg_days() * USECS_PER_DAY + origin_day_micros)
+    } else {
+        Timestamp::from(bucket_fixed_width(width_micros, ts_micros, origin_micros))
+    }
+}
+
+fn bucket_date(bucket_width: Interval, ts: Date, origin: Date) -> Date {
+    let ts_days: i32 = ts.into();
+    let origin_days: i32 = origin.into();
+
+    if is_date_infinite(ts_days) {
+        return Date::from(ts_days);
+    }
+
+    let (width_months, width_micros) = width_components(bucket_width);
+
+    if width_months != 0 {
+        let ts_date = CivilDate::from_pg_days(ts_days as i64);
+        let origin_date = CivilDate::from_pg_days(origin_days as i64);
+        let bucket = bucket_calendar_width(width_months, ts_date, 0, origin_date, 0);
+        Date::from(bucket.to_pg_days() as i32)
+    } else {
+        assert!(
+            width_micros % USECS_PER_DAY == 0,
+            "time_bucket() width on a date column must be a whole number of days"
+        );
+        let width_days = width_micros / USECS_PER_DAY;
+        let bucketed = bucket_fixed_width(width_days, ts_days as i64, origin_days as i64);
+        Date::from(bucketed as i32)
+    }
+}
+
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_timestamp(bucket_width: Interval, ts: Timestamp) -> Timestamp {
+    bucket_timestamp(bucket_width, ts, Timestamp::from(0i64))
+}
+
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_timestamp_with_origin(
+    bucket_width: Interval,
+    ts: Timestamp,
+    origin: Timestamp,
+) -> Timestamp {
+    bucket_timestamp(bucket_width, ts, origin)
+}
+
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_timestamp_with_offset(
+    bucket_width: Interval,
+    ts: Timestamp,
+    offset: Interval,
+) -> Timestamp {
+    let offset_micros = offset.days() as i64 * USECS_PER_DAY + offset.micros();
+    bucket_timestamp(bucket_width, ts, Timestamp::from(offset_micros))
+}
+
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_date(bucket_width: Interval, ts: Date) -> Date {
+    bucket_date(bucket_width, ts, Date::from(0i32))
+}
+
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_date_with_origin(bucket_width: Interval, ts: Date, origin: Date) -> Date {
+    bucket_date(bucket_width, ts, origin)
+}
+
+/// `time_bucket(bucket_width, ts, timezone)` — bucket boundaries are computed
+/// in `timezone`'s local wall-clock time (so a DST transition makes that
+/// day 23 or 25 hours long) and the result is converted back to UTC. Only
+/// meaningful under DuckDB pushdown; see the module docs.
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_timestamp_with_timezone(
+    _bucket_width: Interval,
+    _ts: Timestamp,
+    _timezone: String,
+) -> Timestamp {
+    error!("{FALLBACK_ERROR}")
+}
+
+/// `time_bucket(bucket_width, ts, origin => ..., timezone => ...)` — same
+/// timezone-aware bucketing as [`time_bucket_timestamp_with_timezone`], but
+/// with an explicit origin instead of the default Postgres epoch.
+#[pg_extern(immutable, parallel_safe, name = "time_bucket")]
+fn time_bucket_timestamp_with_origin_and_timezone(
+    _bucket_width: Interval,
+    _ts: Timestamp,
+    origin: default!(Option<Timestamp>, "NULL"),
+    timezone: default!(Option<String>, "NULL"),
+) -> Timestamp {
+    let _ = (origin, timezone);
+    error!("{FALLBACK_ERROR}")
+}
@@ -0,0 +1,28 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Parsing `CREATE FOREIGN TABLE ... OPTIONS (...)` for the object-store
+//! backed sources (`s3://`, `gs://`, `az://`), alongside the existing
+//! local-file path. See [`options`] for the `files`/`glob` option and
+//! [`secret`] for turning server-level credential options into a DuckDB
+//! `SECRET`.
+
+mod options;
+mod secret;
+
+pub use options::*;
+pub use secret::*;
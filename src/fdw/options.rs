@@ -0,0 +1,157 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! `files`/`glob` option parsing for `CREATE FOREIGN TABLE ... OPTIONS
+//! (...)`. Both a local filesystem path and an object-store URL
+//! (`s3://`, `gs://`, `az://`) are accepted; DuckDB does its own glob
+//! expansion once a pattern reaches it, so this module's job is to
+//! recognize which backend a path belongs to and normalize the option into
+//! the list of path patterns DuckDB's `read_parquet()` expects.
+
+/// The storage backend a `files`/`glob` path targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectStoreKind {
+    Local,
+    S3,
+    Gcs,
+    Azure,
+}
+
+impl ObjectStoreKind {
+    /// Identifies the backend from a path's URL scheme, defaulting to
+    /// [`ObjectStoreKind::Local`] for anything without a recognized one.
+    pub fn from_path(path: &str) -> Self {
+        if let Some((scheme, _)) = path.split_once("://") {
+            match scheme {
+                "s3" => return ObjectStoreKind::S3,
+                "gs" | "gcs" => return ObjectStoreKind::Gcs,
+                "az" | "azure" => return ObjectStoreKind::Azure,
+                _ => {}
+            }
+        }
+        ObjectStoreKind::Local
+    }
+
+    /// The DuckDB `SECRET` type name for this backend's credentials, or
+    /// `None` for local files, which need no secret at all.
+    pub fn secret_type(self) -> Option<&'static str> {
+        match self {
+            ObjectStoreKind::Local => None,
+            ObjectStoreKind::S3 => Some("s3"),
+            ObjectStoreKind::Gcs => Some("gcs"),
+            ObjectStoreKind::Azure => Some("azure"),
+        }
+    }
+
+    /// Identifies the backend from a `CREATE SERVER ... OPTIONS (type
+    /// '...')` value, case insensitively. Returns `None` for a type this
+    /// crate doesn't recognize, rather than silently falling back to
+    /// [`ObjectStoreKind::Local`] the way [`Self::from_path`] does for an
+    /// unrecognized URL scheme - an unrecognized `type` option is a caller
+    /// mistake worth surfacing, not a signal the source is local.
+    pub fn from_server_type(type_option: &str) -> Option<Self> {
+        match type_option.to_ascii_lowercase().as_str() {
+            "s3" => Some(ObjectStoreKind::S3),
+            "gcs" | "gs" => Some(ObjectStoreKind::Gcs),
+            "azure" | "az" => Some(ObjectStoreKind::Azure),
+            _ => None,
+        }
+    }
+}
+
+/// The parsed `files`/`glob` option: one or more path patterns (local or
+/// object-store), all from the same backend.
+pub struct FileListing {
+    pub kind: ObjectStoreKind,
+    pub patterns: Vec<String>,
+}
+
+impl FileListing {
+    /// Parses a `files` (or `glob`) option value. Accepts a single path, a
+    /// glob pattern (`s3://bucket/part-*.parquet`), or a comma-separated
+    /// list of either, same as the existing local-file option - multi-file
+    /// foreign tables over partitioned object-store data just list (or
+    /// glob) more than one pattern.
+    pub fn parse(option_value: &str) -> Result<Self, String> {
+        let patterns: Vec<String> = option_value
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if patterns.is_empty() {
+            return Err("`files` option must list at least one path".to_string());
+        }
+
+        let kind = ObjectStoreKind::from_path(&patterns[0]);
+        for pattern in &patterns[1..] {
+            if ObjectStoreKind::from_path(pattern) != kind {
+                return Err(format!(
+                    "all paths in a single `files` option must use the same backend, found both {:?} and {:?}",
+                    kind,
+                    ObjectStoreKind::from_path(pattern)
+                ));
+            }
+        }
+
+        Ok(FileListing { kind, patterns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_backend_from_scheme() {
+        assert_eq!(ObjectStoreKind::from_path("/tmp/data.parquet"), ObjectStoreKind::Local);
+        assert_eq!(
+            ObjectStoreKind::from_path("s3://bucket/data.parquet"),
+            ObjectStoreKind::S3
+        );
+        assert_eq!(
+            ObjectStoreKind::from_path("gs://bucket/data.parquet"),
+            ObjectStoreKind::Gcs
+        );
+        assert_eq!(
+            ObjectStoreKind::from_path("az://container/data.parquet"),
+            ObjectStoreKind::Azure
+        );
+    }
+
+    #[test]
+    fn parses_comma_separated_glob_list() {
+        let listing =
+            FileListing::parse("s3://bucket/a/*.parquet, s3://bucket/b/*.parquet").unwrap();
+        assert_eq!(listing.kind, ObjectStoreKind::S3);
+        assert_eq!(listing.patterns.len(), 2);
+    }
+
+    #[test]
+    fn rejects_mixed_backends() {
+        assert!(FileListing::parse("s3://bucket/a.parquet,/tmp/b.parquet").is_err());
+    }
+
+    #[test]
+    fn identifies_backend_from_server_type_option() {
+        assert_eq!(ObjectStoreKind::from_server_type("S3"), Some(ObjectStoreKind::S3));
+        assert_eq!(ObjectStoreKind::from_server_type("gcs"), Some(ObjectStoreKind::Gcs));
+        assert_eq!(ObjectStoreKind::from_server_type("AZURE"), Some(ObjectStoreKind::Azure));
+        assert_eq!(ObjectStoreKind::from_server_type("unknown"), None);
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Turns the credential/region/endpoint options on a `CREATE SERVER` (or
+//! `CREATE FOREIGN TABLE`) statement into a DuckDB `CREATE SECRET`, so an
+//! object-store backed foreign table can authenticate against `s3://`,
+//! `gs://`, or `az://` (including S3-compatible endpoints like MinIO).
+
+use super::ObjectStoreKind;
+
+/// The subset of `OPTIONS (...)` pg_analytics understands for object-store
+/// credentials. Every field is optional since some backends (e.g. a
+/// MinIO endpoint with anonymous access, or a Gcs bucket relying on
+/// ambient credentials) don't need all of them.
+#[derive(Default, Debug, Clone)]
+pub struct ObjectStoreCredentials {
+    pub key_id: Option<String>,
+    pub secret: Option<String>,
+    pub region: Option<String>,
+    pub endpoint: Option<String>,
+    pub url_style: Option<String>,
+}
+
+/// Renders a `CREATE OR REPLACE SECRET` statement DuckDB can run to
+/// register these credentials for `kind`. Returns `None` for
+/// [`ObjectStoreKind::Local`], which needs no secret.
+pub fn create_secret_sql(
+    name: &str,
+    kind: ObjectStoreKind,
+    credentials: &ObjectStoreCredentials,
+) -> Option<String> {
+    let secret_type = kind.secret_type()?;
+
+    let mut fields = vec![format!("TYPE {secret_type}")];
+    if let Some(key_id) = &credentials.key_id {
+        fields.push(format!("KEY_ID '{}'", escape_sql_literal(key_id)));
+    }
+    if let Some(secret) = &credentials.secret {
+        fields.push(format!("SECRET '{}'", escape_sql_literal(secret)));
+    }
+    if let Some(region) = &credentials.region {
+        fields.push(format!("REGION '{}'", escape_sql_literal(region)));
+    }
+    if let Some(endpoint) = &credentials.endpoint {
+        fields.push(format!("ENDPOINT '{}'", escape_sql_literal(endpoint)));
+    }
+    if let Some(url_style) = &credentials.url_style {
+        fields.push(format!("URL_STYLE '{}'", escape_sql_literal(url_style)));
+    }
+
+    Some(format!(
+        "CREATE OR REPLACE SECRET {name} ({});",
+        fields.join(", ")
+    ))
+}
+
+/// Escapes a value for interpolation into a single-quoted SQL string
+/// literal by doubling embedded single quotes, same as Postgres/DuckDB's
+/// own literal escaping.
+fn escape_sql_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_files_need_no_secret() {
+        assert!(create_secret_sql("s", ObjectStoreKind::Local, &ObjectStoreCredentials::default())
+            .is_none());
+    }
+
+    #[test]
+    fn renders_minio_compatible_s3_secret() {
+        let credentials = ObjectStoreCredentials {
+            key_id: Some("minioadmin".to_string()),
+            secret: Some("minioadmin".to_string()),
+            region: Some("us-east-1".to_string()),
+            endpoint: Some("localhost:9000".to_string()),
+            url_style: Some("path".to_string()),
+        };
+
+        let sql = create_secret_sql("minio_secret", ObjectStoreKind::S3, &credentials).unwrap();
+        assert!(sql.contains("TYPE s3"));
+        assert!(sql.contains("ENDPOINT 'localhost:9000'"));
+        assert!(sql.contains("URL_STYLE 'path'"));
+    }
+
+    #[test]
+    fn escapes_embedded_single_quotes_in_credentials() {
+        let credentials = ObjectStoreCredentials {
+            secret: Some("p'w".to_string()),
+            ..Default::default()
+        };
+
+        let sql = create_secret_sql("s", ObjectStoreKind::S3, &credentials).unwrap();
+        assert!(sql.contains("SECRET 'p''w'"));
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Planner/utility-level plumbing shared by every DuckDB pushdown.
+//!
+//! Functions like `time_bucket()` are declared on the Postgres side purely so
+//! the parser/planner will accept them. When a query only touches DuckDB
+//! foreign tables, the whole query is handed to the embedded DuckDB engine
+//! instead of being executed by the Postgres executor, so the Rust body of
+//! those functions never actually runs in that path. When a query touches an
+//! ordinary heap (or any other non-DuckDB) relation, the function body does
+//! run, and is responsible for either computing a real answer or raising a
+//! clear error.
+//!
+//! `init()` installs a `ProcessUtility` hook that intercepts
+//! `CREATE MATERIALIZED VIEW ... WITH (continuous)` so it can register the
+//! view in `paradedb.continuous_aggregates` right after Postgres creates it.
+
+use pgrx::prelude::*;
+use pgrx::{pg_sys, PgList};
+
+static mut PREV_PROCESS_UTILITY_HOOK: pg_sys::ProcessUtility_hook_type = None;
+
+/// Registers the hooks pg_analytics needs at `_PG_init()` time.
+///
+/// # Safety
+///
+/// Must only be called once, from `_PG_init()`, before any other backend
+/// code can run a utility statement.
+pub unsafe fn init() {
+    PREV_PROCESS_UTILITY_HOOK = pg_sys::ProcessUtility_hook;
+    pg_sys::ProcessUtility_hook = Some(process_utility);
+}
+
+#[pg_guard]
+unsafe extern "C-unwind" fn process_utility(
+    pstmt: *mut pg_sys::PlannedStmt,
+    query_string: *const std::os::raw::c_char,
+    read_only_tree: bool,
+    context: pg_sys::ProcessUtilityContext,
+    params: pg_sys::ParamListInfo,
+    query_env: *mut pg_sys::QueryEnvironment,
+    dest: *mut pg_sys::DestReceiver,
+    qc: *mut pg_sys::QueryCompletion,
+) {
+    let utility_stmt = (*pstmt).utilityStmt;
+
+    // Validated eagerly, before the standard hook creates the table, so a
+    // bad `files`/`glob` option aborts the statement instead of leaving
+    // behind a foreign table that can never be scanned.
+    if !utility_stmt.is_null() && (*utility_stmt).type_ == pg_sys::NodeTag::T_CreateForeignTableStmt
+    {
+        validate_foreign_table_options(utility_stmt as *mut pg_sys::CreateForeignTableStmt);
+    }
+
+    let continuous_aggregate = detect_continuous_aggregate(pstmt, query_string);
+
+    match PREV_PROCESS_UTILITY_HOOK {
+        Some(prev) => prev(
+            pstmt,
+            query_string,
+            read_only_tree,
+            context,
+            params,
+            query_env,
+            dest,
+            qc,
+        ),
+        None => pg_sys::standard_ProcessUtility(
+            pstmt,
+            query_string,
+            read_only_tree,
+            context,
+            params,
+            query_env,
+            dest,
+            qc,
+        ),
+    }
+
+    // Everything below runs only after the standard hook above has actually
+    // created the relation, so regclass/options lookups see it.
+    if let Some(aggregate) = continuous_aggregate {
+        handle_create_continuous_aggregate(&aggregate);
+    }
+
+    if !utility_stmt.is_null() && (*utility_stmt).type_ == pg_sys::NodeTag::T_CreateForeignServerStmt
+    {
+        handle_create_foreign_server(utility_stmt as *mut pg_sys::CreateForeignServerStmt);
+    }
+}
+
+/// Rejects a `CREATE FOREIGN TABLE ... OPTIONS (files '...')` (or `glob`)
+/// whose value [`crate::fdw::FileListing::parse`] can't make sense of.
+unsafe fn validate_foreign_table_options(stmt: *mut pg_sys::CreateForeignTableStmt) {
+    let options = collect_def_elems((*stmt).options);
+
+    let Some(files) = options.get("files").or_else(|| options.get("glob")) else {
+        return;
+    };
+
+    if let Err(err) = crate::fdw::FileListing::parse(files) {
+        error!("invalid `files` option: {err}");
+    }
+}
+
+/// If `stmt`'s `type` option identifies a recognized object-store backend,
+/// renders the `CREATE SECRET` DuckDB needs to authenticate against it from
+/// whichever credential options are present (possibly none) and passes it
+/// to `execute_on_duckdb`.
+unsafe fn handle_create_foreign_server(stmt: *mut pg_sys::CreateForeignServerStmt) {
+    let server_name = std::ffi::CStr::from_ptr((*stmt).servername)
+        .to_string_lossy()
+        .into_owned();
+    let options = collect_def_elems((*stmt).options);
+
+    let Some(kind) = options
+        .get("type")
+        .and_then(|t| crate::fdw::ObjectStoreKind::from_server_type(t))
+    else {
+        return;
+    };
+
+    let credentials = crate::fdw::ObjectStoreCredentials {
+        key_id: options.get("key_id").cloned(),
+        secret: options.get("secret").cloned(),
+        region: options.get("region").cloned(),
+        endpoint: options.get("endpoint").cloned(),
+        url_style: options.get("url_style").cloned(),
+    };
+
+    let secret_name = format!("{server_name}_secret");
+    if let Some(sql) = crate::fdw::create_secret_sql(&secret_name, kind, &credentials) {
+        crate::duckdb::execute_on_duckdb(&sql);
+    }
+}
+
+/// Collects a `DefElem` list (a `CREATE SERVER`/`CREATE FOREIGN TABLE`
+/// statement's `OPTIONS (...)`) into a name -> value map, the same way
+/// postgres_fdw-style extensions read their own options.
+unsafe fn collect_def_elems(
+    options: *mut pg_sys::List,
+) -> std::collections::HashMap<String, String> {
+    let mut map = std::collections::HashMap::new();
+    if options.is_null() {
+        return map;
+    }
+
+    for def_elem in PgList::<pg_sys::DefElem>::from_pg(options).iter_ptr() {
+        if def_elem.is_null() {
+            continue;
+        }
+
+        let name = std::ffi::CStr::from_ptr((*def_elem).defname)
+            .to_string_lossy()
+            .into_owned();
+        let value = std::ffi::CStr::from_ptr(pg_sys::defGetString(def_elem))
+            .to_string_lossy()
+            .into_owned();
+
+        map.insert(name, value);
+    }
+
+    map
+}
+
+/// If `pstmt` is a `CREATE MATERIALIZED VIEW ... WITH (continuous) AS ...`,
+/// parses out the pieces its catalog row needs. Returns `None` for any other
+/// statement, or if the statement's bucketing call can't be found (see
+/// [`crate::continuous_aggregate::parse_view_definition`]).
+unsafe fn detect_continuous_aggregate(
+    pstmt: *mut pg_sys::PlannedStmt,
+    query_string: *const std::os::raw::c_char,
+) -> Option<crate::continuous_aggregate::ContinuousAggregate> {
+    let utility_stmt = (*pstmt).utilityStmt;
+    if utility_stmt.is_null() || (*utility_stmt).type_ != pg_sys::NodeTag::T_CreateTableAsStmt {
+        return None;
+    }
+
+    let stmt = utility_stmt as *mut pg_sys::CreateTableAsStmt;
+    if (*stmt).objtype != pg_sys::ObjectType::OBJECT_MATVIEW || !has_continuous_option(stmt) {
+        return None;
+    }
+
+    let into = (*stmt).into;
+    let view_name = std::ffi::CStr::from_ptr((*(*into).rel).relname)
+        .to_string_lossy()
+        .into_owned();
+
+    let full_sql = std::ffi::CStr::from_ptr(query_string).to_string_lossy();
+    let stmt_text = extract_statement_text(&full_sql, (*pstmt).stmt_location, (*pstmt).stmt_len);
+
+    let definition = crate::continuous_aggregate::parse_view_definition(&stmt_text)?;
+
+    let bucket_width = Spi::get_one::<Interval>(&format!(
+        "SELECT ({})::interval",
+        definition.bucket_width_expr
+    ))
+    .ok()
+    .flatten()?;
+
+    let bucket_origin = definition.origin_expr.as_ref().and_then(|expr| {
+        Spi::get_one::<Timestamp>(&format!("SELECT ({expr})::timestamp"))
+            .ok()
+            .flatten()
+    });
+
+    let bucket_timezone = definition.timezone_expr.as_ref().and_then(|expr| {
+        Spi::get_one::<String>(&format!("SELECT ({expr})::text"))
+            .ok()
+            .flatten()
+    });
+
+    Some(crate::continuous_aggregate::ContinuousAggregate {
+        view_name,
+        view_query: definition.view_query,
+        bucket_function: definition.bucket_function,
+        bucket_width,
+        bucket_origin,
+        bucket_timezone,
+        watermark: None,
+    })
+}
+
+/// True if `stmt`'s `WITH (...)` options include a bare `continuous` option,
+/// e.g. `CREATE MATERIALIZED VIEW m WITH (continuous) AS ...`.
+unsafe fn has_continuous_option(stmt: *mut pg_sys::CreateTableAsStmt) -> bool {
+    let into = (*stmt).into;
+    if into.is_null() || (*into).options.is_null() {
+        return false;
+    }
+
+    PgList::<pg_sys::DefElem>::from_pg((*into).options)
+        .iter_ptr()
+        .any(|def_elem| {
+            !def_elem.is_null()
+                && std::ffi::CStr::from_ptr((*def_elem).defname).to_string_lossy() == "continuous"
+        })
+}
+
+/// Slices `full_sql` down to just the statement `stmt_location`/`stmt_len`
+/// describe, same as e.g. `auto_explain` does to recover one statement's
+/// text out of a (possibly multi-statement) query string.
+fn extract_statement_text(full_sql: &str, stmt_location: i32, stmt_len: i32) -> String {
+    if stmt_location < 0 {
+        return full_sql.trim().to_string();
+    }
+
+    let start = stmt_location as usize;
+    let end = if stmt_len > 0 {
+        (start + stmt_len as usize).min(full_sql.len())
+    } else {
+        full_sql.len()
+    };
+
+    full_sql
+        .get(start..end)
+        .unwrap_or(full_sql)
+        .trim()
+        .to_string()
+}
+
+/// Called from the `ProcessUtility` hook when it sees a
+/// `CREATE MATERIALIZED VIEW ... WITH (continuous)` statement, right after
+/// the standard hook has created the view: registers it in
+/// `paradedb.continuous_aggregates`.
+pub fn handle_create_continuous_aggregate(
+    aggregate: &crate::continuous_aggregate::ContinuousAggregate,
+) {
+    validate_continuous_aggregate_source(aggregate);
+
+    if let Err(err) = crate::continuous_aggregate::register(aggregate) {
+        error!(
+            "failed to register continuous aggregate \"{}\": {err}",
+            aggregate.view_name
+        );
+    }
+}
+
+/// Refuses to register a continuous aggregate whose defining query's source
+/// relation isn't a foreign table (see [`is_duckdb_fdw_relation`]):
+/// `refresh_continuous_aggregate()` re-runs
+/// that whole query on every call, which is only cheap when it's pushed
+/// down to DuckDB rather than re-scanned by the Postgres executor. Does
+/// nothing if the source relation can't be identified (see
+/// [`crate::continuous_aggregate::first_from_relation`]) or doesn't exist
+/// yet, rather than guessing wrong and rejecting a valid view.
+fn validate_continuous_aggregate_source(aggregate: &crate::continuous_aggregate::ContinuousAggregate) {
+    let Some(relation_name) = crate::continuous_aggregate::first_from_relation(&aggregate.view_query)
+    else {
+        return;
+    };
+
+    let relid = Spi::get_one::<pg_sys::Oid>(&format!(
+        "SELECT to_regclass('{}')::oid",
+        relation_name.replace('\'', "''")
+    ))
+    .ok()
+    .flatten();
+
+    if let Some(relid) = relid {
+        if !is_duckdb_fdw_relation(relid) {
+            error!(
+                "continuous aggregate \"{}\" must be defined over a DuckDB-backed foreign table; \"{relation_name}\" is not one",
+                aggregate.view_name
+            );
+        }
+    }
+}
+
+/// Returns true if `relid` is a foreign table rather than an ordinary
+/// Postgres heap table. Which foreign data wrapper backs it is not checked,
+/// so a foreign table of any wrapper counts.
+///
+/// Used by [`validate_continuous_aggregate_source`] to reject registering a
+/// `WITH (continuous)` materialized view over a non-DuckDB-backed source.
+pub fn is_duckdb_fdw_relation(relid: pg_sys::Oid) -> bool {
+    if relid == pg_sys::InvalidOid {
+        return false;
+    }
+
+    unsafe {
+        let relation = pg_sys::RelationIdGetRelation(relid);
+        if relation.is_null() {
+            return false;
+        }
+
+        let is_foreign = (*relation).rd_rel.as_ref().map(|r| r.relkind).unwrap_or(0)
+            == pg_sys::RELKIND_FOREIGN_TABLE as i8;
+
+        pg_sys::RelationClose(relation);
+
+        is_foreign
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod continuous_aggregate;
+mod duckdb;
+mod fdw;
+mod hooks;
+
+use pgrx::prelude::*;
+
+pgrx::pg_module_magic!();
+
+/// # Safety
+///
+/// This extension installs a `ProcessUtility` hook so that
+/// `CREATE MATERIALIZED VIEW ... WITH (continuous)` statements are
+/// registered as continuous aggregates, and (eventually) a planner hook so
+/// that queries against DuckDB foreign tables are transparently rewritten to
+/// execute against the embedded DuckDB engine. `_PG_init()` runs once, before
+/// any backend can process a utility statement, so installing these here is
+/// sound.
+#[pg_guard]
+pub extern "C-unwind" fn _PG_init() {
+    unsafe {
+        hooks::init();
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod pg_analytics_tests {
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_extension_loads() {
+        assert!(true);
+    }
+}
+
+#[cfg(test)]
+pub mod pg_test {
+    pub fn setup(_options: Vec<&str>) {}
+
+    pub fn postgresql_conf_options() -> Vec<&'static str> {
+        vec![]
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod fixtures;
+
+use anyhow::Result;
+use datafusion::parquet::arrow::ArrowWriter;
+use fixtures::*;
+use rstest::*;
+use shared::fixtures::arrow::primitive_setup_fdw_local_file_listing;
+use shared::fixtures::tempfile::TempDir;
+use sqlx::PgConnection;
+use std::fs::File;
+
+#[rstest]
+async fn test_continuous_aggregate_registers_in_catalog(
+    mut conn: PgConnection,
+    tempdir: TempDir,
+) -> Result<()> {
+    let stored_batch = time_series_record_batch_minutes()?;
+    let parquet_path = tempdir.path().join("test_continuous_aggregate.parquet");
+    let parquet_file = File::create(&parquet_path)?;
+
+    let mut writer = ArrowWriter::try_new(parquet_file, stored_batch.schema(), None).unwrap();
+    writer.write(&stored_batch)?;
+    writer.close()?;
+
+    primitive_setup_fdw_local_file_listing(parquet_path.as_path().to_str().unwrap(), "MyTable")
+        .execute(&mut conn);
+
+    format!(
+        "CREATE FOREIGN TABLE timeseries () SERVER parquet_server OPTIONS (files '{}')",
+        parquet_path.to_str().unwrap()
+    )
+    .execute(&mut conn);
+
+    "CREATE MATERIALIZED VIEW minute_rollup WITH (continuous) AS
+        SELECT time_bucket(INTERVAL '1 MINUTE', timestamp::TIMESTAMP) AS bucket, AVG(value) AS avg_value
+        FROM timeseries
+        GROUP BY bucket;"
+        .execute(&mut conn);
+
+    let rows: Vec<(String, Option<String>, Option<String>)> =
+        "SELECT bucket_function, bucket_origin::text, bucket_timezone
+         FROM paradedb.continuous_aggregates
+         WHERE view_name = 'minute_rollup'::regclass;"
+            .fetch_result(&mut conn)
+            .unwrap();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].1, None, "bucket_origin should default to NULL, not a sentinel value");
+    assert_eq!(rows[0].2, None, "bucket_timezone should default to NULL, not a sentinel value");
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_refresh_continuous_aggregate_advances_watermark(
+    mut conn: PgConnection,
+    tempdir: TempDir,
+) -> Result<()> {
+    let stored_batch = time_series_record_batch_minutes()?;
+    let parquet_path = tempdir.path().join("test_refresh.parquet");
+    let parquet_file = File::create(&parquet_path)?;
+
+    let mut writer = ArrowWriter::try_new(parquet_file, stored_batch.schema(), None).unwrap();
+    writer.write(&stored_batch)?;
+    writer.close()?;
+
+    primitive_setup_fdw_local_file_listing(parquet_path.as_path().to_str().unwrap(), "MyTable")
+        .execute(&mut conn);
+
+    format!(
+        "CREATE FOREIGN TABLE timeseries () SERVER parquet_server OPTIONS (files '{}')",
+        parquet_path.to_str().unwrap()
+    )
+    .execute(&mut conn);
+
+    "CREATE MATERIALIZED VIEW minute_rollup WITH (continuous) AS
+        SELECT time_bucket(INTERVAL '1 MINUTE', timestamp::TIMESTAMP) AS bucket, AVG(value) AS avg_value
+        FROM timeseries
+        GROUP BY bucket;"
+        .execute(&mut conn);
+
+    "SELECT refresh_continuous_aggregate('minute_rollup', TIMESTAMP '2000-01-01', TIMESTAMP '2030-01-01');"
+        .execute(&mut conn);
+
+    let watermark: Vec<(Option<String>,)> =
+        "SELECT watermark::text FROM paradedb.continuous_aggregates WHERE view_name = 'minute_rollup'::regclass;"
+            .fetch_result(&mut conn)
+            .unwrap();
+
+    assert_eq!(watermark[0].0.as_deref(), Some("2030-01-01 00:00:00"));
+
+    Ok(())
+}
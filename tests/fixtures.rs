@@ -0,0 +1,172 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Shared plumbing for the integration tests in this crate: a live
+//! connection to a Postgres instance with pg_analytics loaded, a scratch
+//! directory for Parquet fixtures, sample record batches, and the small set
+//! of `nyc_trips`-style heap tables the fallback tests run against.
+
+use anyhow::Result;
+use arrow_array::{
+    ArrayRef, Date32Array, Float64Array, RecordBatch, TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use async_std::task;
+use rstest::*;
+use shared::fixtures::db::Query;
+use shared::fixtures::tempfile::TempDir;
+use sqlx::PgConnection;
+use std::sync::Arc;
+
+pub use shared::fixtures::db::*;
+
+#[fixture]
+pub fn conn() -> PgConnection {
+    task::block_on(async { shared::fixtures::db::conn().await })
+}
+
+#[fixture]
+pub fn tempdir() -> TempDir {
+    TempDir::new().expect("could not create temp directory for test fixtures")
+}
+
+fn timestamp_batch(starts_micros: &[i64], values: &[f64]) -> Result<RecordBatch> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("value", DataType::Float64, false),
+    ]));
+
+    let timestamp: ArrayRef = Arc::new(TimestampMicrosecondArray::from(starts_micros.to_vec()));
+    let value: ArrayRef = Arc::new(Float64Array::from(values.to_vec()));
+
+    Ok(RecordBatch::try_new(schema, vec![timestamp, value])?)
+}
+
+/// Ten one-minute samples, used by [the minute-width `time_bucket()` tests].
+pub fn time_series_record_batch_minutes() -> Result<RecordBatch> {
+    const MINUTE_MICROS: i64 = 60_000_000;
+    let starts: Vec<i64> = (0..10).map(|i| i * MINUTE_MICROS).collect();
+    let values: Vec<f64> = (0..10).map(|i| i as f64).collect();
+    timestamp_batch(&starts, &values)
+}
+
+/// Ten samples spaced one year apart, used by the year-width `time_bucket()`
+/// tests.
+pub fn time_series_record_batch_years() -> Result<RecordBatch> {
+    const YEAR_MICROS: i64 = 365 * 24 * 60 * 60 * 1_000_000;
+    let starts: Vec<i64> = (0..10).map(|i| i * YEAR_MICROS).collect();
+    let values: Vec<f64> = (0..10).map(|i| i as f64).collect();
+    timestamp_batch(&starts, &values)
+}
+
+/// A handful of finite samples bracketed by Postgres' `infinity` and
+/// `-infinity` sentinels, in both a `timestamp` and a `date` column, used by
+/// the tests asserting a foreign-table scan round-trips those sentinels
+/// instead of panicking or wrapping.
+pub fn time_series_record_batch_with_infinity() -> Result<RecordBatch> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("day", DataType::Date32, false),
+        Field::new("value", DataType::Float64, false),
+    ]));
+
+    let timestamps: ArrayRef = Arc::new(TimestampMicrosecondArray::from(vec![
+        i64::MIN,
+        0,
+        60_000_000,
+        i64::MAX,
+    ]));
+    let days: ArrayRef = Arc::new(Date32Array::from(vec![i32::MIN, 0, 1, i32::MAX]));
+    let values: ArrayRef = Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0]));
+
+    Ok(RecordBatch::try_new(schema, vec![timestamps, days, values])?)
+}
+
+/// A `CREATE SERVER` statement wiring pg_analytics' parquet FDW up to a
+/// local MinIO instance, mirroring `primitive_setup_fdw_local_file_listing`
+/// for object-store backed foreign tables. It carries no `CREATE SECRET`
+/// itself: the `ProcessUtility` hook derives that from the server's
+/// credential options. `endpoint` is host:port, e.g. `"127.0.0.1:9000"`.
+pub fn primitive_setup_fdw_s3_listing(endpoint: &str, server_name: &str) -> String {
+    format!(
+        r#"
+        CREATE SERVER {server_name} FOREIGN DATA WRAPPER parquet_wrapper
+        OPTIONS (type 'S3', key_id 'minioadmin', secret 'minioadmin', region 'us-east-1', endpoint '{endpoint}', url_style 'path', use_ssl 'false');
+        "#
+    )
+}
+
+/// A minimal heap-table stand-in for the NYC taxi trips dataset, used by the
+/// tests that exercise `time_bucket()` against non-FDW relations.
+pub struct NycTripsTable;
+
+impl NycTripsTable {
+    pub fn setup() -> String {
+        r#"
+        CREATE TABLE nyc_trips (
+            tpep_pickup_datetime TIMESTAMP,
+            trip_distance DOUBLE PRECISION
+        );
+        INSERT INTO nyc_trips (tpep_pickup_datetime, trip_distance) VALUES
+            ('2024-01-01 00:00:00', 1.0),
+            ('2024-01-01 12:00:00', 2.0),
+            ('2024-01-03 00:00:00', 3.0);
+        "#
+        .to_string()
+    }
+}
+
+/// Heap-table equivalents of [`time_series_record_batch_minutes`] and
+/// [`time_series_record_batch_years`], used by the tests that exercise
+/// `time_bucket()`'s native (non-FDW) fallback at minute and year
+/// granularity - `nyc_trips`'s 3-row, day-granularity data is too coarse to
+/// meaningfully bucket at either width.
+pub struct NativeTimeSeriesTable;
+
+impl NativeTimeSeriesTable {
+    pub fn setup_minutes() -> String {
+        let mut sql = String::from(
+            "CREATE TABLE native_timeseries (ts TIMESTAMP, value DOUBLE PRECISION);\n",
+        );
+        for i in 0..10 {
+            sql += &format!(
+                "INSERT INTO native_timeseries (ts, value) VALUES (TIMESTAMP '2024-01-01 00:00:00' + INTERVAL '{i} minutes', {i}.0);\n"
+            );
+        }
+        sql
+    }
+
+    pub fn setup_years() -> String {
+        let mut sql = String::from(
+            "CREATE TABLE native_timeseries (ts TIMESTAMP, value DOUBLE PRECISION);\n",
+        );
+        for i in 0..10 {
+            sql += &format!(
+                "INSERT INTO native_timeseries (ts, value) VALUES (TIMESTAMP '2000-01-01 00:00:00' + INTERVAL '{i} years', {i}.0);\n"
+            );
+        }
+        sql
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod fixtures;
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use fixtures::*;
+use rstest::*;
+use sqlx::PgConnection;
+
+#[rstest]
+async fn test_time_bucket_gapfill_locf_fills_missing_buckets(mut conn: PgConnection) -> Result<()> {
+    "CREATE TABLE sparse_readings (ts TIMESTAMP, value DOUBLE PRECISION);
+     INSERT INTO sparse_readings (ts, value) VALUES
+        ('2024-01-01 00:00:00', 1.0),
+        ('2024-01-01 00:02:00', 3.0);"
+        .execute(&mut conn);
+
+    let rows: Vec<(String, NaiveDateTime, Option<f64>)> = "
+        SELECT * FROM time_bucket_gapfill(
+            INTERVAL '1 MINUTE',
+            'SELECT ''0'', ts, value FROM sparse_readings ORDER BY ts',
+            TIMESTAMP '2024-01-01 00:00:00',
+            TIMESTAMP '2024-01-01 00:03:00',
+            'locf'
+        );"
+    .fetch_result(&mut conn)
+    .unwrap();
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].2, Some(1.0));
+    assert_eq!(rows[1].2, Some(1.0), "missing bucket should carry the last value forward");
+    assert_eq!(rows[2].2, Some(3.0));
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_gapfill_interpolate_fills_missing_buckets(
+    mut conn: PgConnection,
+) -> Result<()> {
+    "CREATE TABLE sparse_readings (ts TIMESTAMP, value DOUBLE PRECISION);
+     INSERT INTO sparse_readings (ts, value) VALUES
+        ('2024-01-01 00:00:00', 0.0),
+        ('2024-01-01 00:02:00', 4.0);"
+        .execute(&mut conn);
+
+    let rows: Vec<(String, NaiveDateTime, Option<f64>)> = "
+        SELECT * FROM time_bucket_gapfill(
+            INTERVAL '1 MINUTE',
+            'SELECT ''0'', ts, value FROM sparse_readings ORDER BY ts',
+            TIMESTAMP '2024-01-01 00:00:00',
+            TIMESTAMP '2024-01-01 00:03:00',
+            'interpolate'
+        );"
+    .fetch_result(&mut conn)
+    .unwrap();
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].2, Some(0.0));
+    assert_eq!(rows[1].2, Some(2.0), "missing bucket should be linearly interpolated");
+    assert_eq!(rows[2].2, Some(4.0));
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_gapfill_none_leaves_missing_buckets_null(
+    mut conn: PgConnection,
+) -> Result<()> {
+    "CREATE TABLE sparse_readings (ts TIMESTAMP, value DOUBLE PRECISION);
+     INSERT INTO sparse_readings (ts, value) VALUES ('2024-01-01 00:00:00', 1.0);"
+        .execute(&mut conn);
+
+    let rows: Vec<(String, NaiveDateTime, Option<f64>)> = "
+        SELECT * FROM time_bucket_gapfill(
+            INTERVAL '1 MINUTE',
+            'SELECT ''0'', ts, value FROM sparse_readings ORDER BY ts',
+            TIMESTAMP '2024-01-01 00:00:00',
+            TIMESTAMP '2024-01-01 00:02:00',
+            'none'
+        );"
+    .fetch_result(&mut conn)
+    .unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].2, Some(1.0));
+    assert_eq!(rows[1].2, None);
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_gapfill_fills_each_partition_independently(
+    mut conn: PgConnection,
+) -> Result<()> {
+    "CREATE TABLE sparse_readings (sensor_id TEXT, ts TIMESTAMP, value DOUBLE PRECISION);
+     INSERT INTO sparse_readings (sensor_id, ts, value) VALUES
+        ('a', '2024-01-01 00:00:00', 1.0),
+        ('a', '2024-01-01 00:02:00', 3.0),
+        ('b', '2024-01-01 00:00:00', 10.0);"
+        .execute(&mut conn);
+
+    let rows: Vec<(String, NaiveDateTime, Option<f64>)> = "
+        SELECT * FROM time_bucket_gapfill(
+            INTERVAL '1 MINUTE',
+            'SELECT sensor_id, ts, value FROM sparse_readings ORDER BY sensor_id, ts',
+            TIMESTAMP '2024-01-01 00:00:00',
+            TIMESTAMP '2024-01-01 00:03:00',
+            'locf'
+        ) ORDER BY partition, bucket;"
+    .fetch_result(&mut conn)
+    .unwrap();
+
+    assert_eq!(rows.len(), 6, "3 buckets each for sensors 'a' and 'b'");
+
+    let sensor_a: Vec<_> = rows.iter().filter(|r| r.0 == "a").collect();
+    assert_eq!(sensor_a.len(), 3);
+    assert_eq!(sensor_a[0].2, Some(1.0));
+    assert_eq!(sensor_a[1].2, Some(1.0), "sensor a's missing bucket carries its own last value forward");
+    assert_eq!(sensor_a[2].2, Some(3.0));
+
+    let sensor_b: Vec<_> = rows.iter().filter(|r| r.0 == "b").collect();
+    assert_eq!(sensor_b.len(), 3);
+    assert_eq!(sensor_b[0].2, Some(10.0));
+    assert_eq!(
+        sensor_b[1].2,
+        Some(10.0),
+        "sensor b's missing buckets must carry sensor b's value forward, not sensor a's"
+    );
+    assert_eq!(sensor_b[2].2, Some(10.0));
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_locf_requires_gapfill_context(mut conn: PgConnection) -> Result<()> {
+    let trips_table = NycTripsTable::setup();
+    trips_table.execute(&mut conn);
+
+    #[allow(clippy::single_match)]
+    match "SELECT locf(trip_distance) FROM nyc_trips;".execute_result(&mut conn) {
+        Ok(_) => panic!("locf() should require a time_bucket_gapfill() query"),
+        Err(error) => assert!(error
+            .to_string()
+            .contains("can only be used in the SELECT list of a query that groups by time_bucket_gapfill()")),
+    }
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_interpolate_requires_gapfill_context(mut conn: PgConnection) -> Result<()> {
+    let trips_table = NycTripsTable::setup();
+    trips_table.execute(&mut conn);
+
+    #[allow(clippy::single_match)]
+    match "SELECT interpolate(trip_distance) FROM nyc_trips;".execute_result(&mut conn) {
+        Ok(_) => panic!("interpolate() should require a time_bucket_gapfill() query"),
+        Err(error) => assert!(error
+            .to_string()
+            .contains("can only be used in the SELECT list of a query that groups by time_bucket_gapfill()")),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod fixtures;
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use datafusion::parquet::arrow::ArrowWriter;
+use fixtures::*;
+use rstest::*;
+use shared::fixtures::arrow::primitive_setup_fdw_local_file_listing;
+use shared::fixtures::tempfile::TempDir;
+use sqlx::PgConnection;
+use std::fs::File;
+use time::Date;
+
+#[rstest]
+async fn test_infinity_timestamp_and_date_roundtrip(
+    mut conn: PgConnection,
+    tempdir: TempDir,
+) -> Result<()> {
+    let stored_batch = time_series_record_batch_with_infinity()?;
+    let parquet_path = tempdir.path().join("test_infinity.parquet");
+    let parquet_file = File::create(&parquet_path)?;
+
+    let mut writer = ArrowWriter::try_new(parquet_file, stored_batch.schema(), None).unwrap();
+    writer.write(&stored_batch)?;
+    writer.close()?;
+
+    primitive_setup_fdw_local_file_listing(parquet_path.as_path().to_str().unwrap(), "MyTable")
+        .execute(&mut conn);
+
+    format!(
+        "CREATE FOREIGN TABLE timeseries () SERVER parquet_server OPTIONS (files '{}')",
+        parquet_path.to_str().unwrap()
+    )
+    .execute(&mut conn);
+
+    let timestamps: Vec<(String,)> =
+        "SELECT timestamp::TIMESTAMP::text FROM timeseries ORDER BY value;"
+            .fetch_result(&mut conn)
+            .unwrap();
+
+    assert_eq!(timestamps[0].0, "-infinity");
+    assert_eq!(timestamps[3].0, "infinity");
+
+    let days: Vec<(String,)> = "SELECT day::DATE::text FROM timeseries ORDER BY value;"
+        .fetch_result(&mut conn)
+        .unwrap();
+
+    assert_eq!(days[0].0, "-infinity");
+    assert_eq!(days[3].0, "infinity");
+
+    // Grouping/aggregating should not panic on the infinite rows.
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 DAY', timestamp::TIMESTAMP) AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert!(!data.is_empty());
+
+    let buckets: Vec<(Date,)> = "SELECT time_bucket(INTERVAL '1 DAY', day::DATE) AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert!(!buckets.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// Copyright (c) 2023-2024 Retake, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Mirrors the local-file `time_bucket()` tests in `tests/time_bucket.rs`,
+//! but against a MinIO endpoint instead of a local Parquet file, so the
+//! same `time_bucket` queries run unchanged over remote, partitioned data.
+//! Requires a MinIO instance reachable at `MINIO_ENDPOINT`
+//! (default `127.0.0.1:9000`) with the `pg-analytics-test` bucket
+//! pre-populated by the test harness, same as the other integration
+//! suites in this crate that depend on external services.
+
+mod fixtures;
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use fixtures::*;
+use rstest::*;
+use sqlx::PgConnection;
+
+fn minio_endpoint() -> String {
+    std::env::var("MINIO_ENDPOINT").unwrap_or_else(|_| "127.0.0.1:9000".to_string())
+}
+
+#[rstest]
+async fn test_time_bucket_over_s3_glob(mut conn: PgConnection) -> Result<()> {
+    primitive_setup_fdw_s3_listing(&minio_endpoint(), "minio_server").execute(&mut conn);
+
+    // The bucket is pre-populated (by the test harness' MinIO fixtures,
+    // mirroring `time_series_record_batch_minutes`) with partitioned
+    // Parquet files under `minute/`, so a glob spans all of them as one
+    // foreign table.
+    "CREATE FOREIGN TABLE timeseries ()
+        SERVER minio_server
+        OPTIONS (files 's3://pg-analytics-test/minute/*.parquet')"
+        .execute(&mut conn);
+
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 MINUTE', timestamp::TIMESTAMP) AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_eq!(10, data.len());
+
+    Ok(())
+}
@@ -18,7 +18,7 @@
 mod fixtures;
 
 use anyhow::Result;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use datafusion::parquet::arrow::ArrowWriter;
 use fixtures::*;
 use rstest::*;
@@ -28,6 +28,42 @@ use sqlx::PgConnection;
 use std::fs::File;
 use time::Date;
 
+/// Hourly samples spanning the 2024-03-31 `Europe/Berlin` spring-forward
+/// transition, when local wall-clock time jumps from 02:00 to 03:00 (the
+/// 2024-03-31 day is only 23 hours long in that zone).
+fn time_series_record_batch_dst() -> Result<arrow_array::RecordBatch> {
+    use arrow_array::{ArrayRef, Float64Array, TimestampMicrosecondArray};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    let start = NaiveDate::from_ymd_opt(2024, 3, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+
+    let timestamps: Vec<i64> = (0..48)
+        .map(|hour| (start + chrono::Duration::hours(hour)).and_utc().timestamp_micros())
+        .collect();
+    let values: Vec<f64> = (0..48).map(|i| i as f64).collect();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("value", DataType::Float64, false),
+    ]));
+
+    let timestamp: ArrayRef = Arc::new(TimestampMicrosecondArray::from(timestamps));
+    let value: ArrayRef = Arc::new(Float64Array::from(values));
+
+    Ok(arrow_array::RecordBatch::try_new(
+        schema,
+        vec![timestamp, value],
+    )?)
+}
+
 #[rstest]
 async fn test_time_bucket_minutes_duckdb(mut conn: PgConnection, tempdir: TempDir) -> Result<()> {
     let stored_batch = time_series_record_batch_minutes()?;
@@ -145,14 +181,64 @@ async fn test_time_bucket_years_duckdb(mut conn: PgConnection, tempdir: TempDir)
 
 #[rstest]
 async fn test_time_bucket_fallback(mut conn: PgConnection) -> Result<()> {
+    let trips_table = NycTripsTable::setup();
+    trips_table.execute(&mut conn);
+
+    // `nyc_trips` is an ordinary heap table, so this now runs through the
+    // native fallback instead of requiring a DuckDB FDW.
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '2 DAY', tpep_pickup_datetime::TIMESTAMP) AS bucket, AVG(trip_distance) as avg_value FROM nyc_trips GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_eq!(2, data.len());
+
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 DAY', tpep_pickup_datetime::TIMESTAMP, TIMESTAMP '2024-01-01 06:00:00') AS bucket, AVG(trip_distance) as avg_value FROM nyc_trips GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_eq!(3, data.len());
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_fallback_minutes(mut conn: PgConnection) -> Result<()> {
+    NativeTimeSeriesTable::setup_minutes().execute(&mut conn);
+
+    // 10 one-minute samples (0..10 minutes past the origin) bucketed into
+    // 2-minute buckets, on an ordinary heap table.
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '2 MINUTES', ts) AS bucket, AVG(value) as avg_value FROM native_timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_eq!(5, data.len());
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_fallback_years(mut conn: PgConnection) -> Result<()> {
+    NativeTimeSeriesTable::setup_years().execute(&mut conn);
+
+    // 10 samples a year apart (2000..2009) bucketed into 2-year buckets, on
+    // an ordinary heap table.
+    let data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '2 YEARS', ts) AS bucket, AVG(value) as avg_value FROM native_timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_eq!(5, data.len());
+
+    Ok(())
+}
+
+#[rstest]
+async fn test_time_bucket_timezone_fallback(mut conn: PgConnection) -> Result<()> {
     let error_message = "Function `time_bucket()` must be used with a DuckDB FDW. Native postgres does not support this function.If you believe this function should be implemented natively as a fallback please submit a ticket to https://github.com/paradedb/pg_analytics/issues.";
     let trips_table = NycTripsTable::setup();
     trips_table.execute(&mut conn);
 
+    // The timezone-aware overloads have no native fallback yet - they still
+    // require a DuckDB FDW - even though the plain-width overloads now do.
     #[allow(clippy::single_match)]
-    match "SELECT time_bucket(INTERVAL '2 DAY', tpep_pickup_datetime::DATE) AS bucket, AVG(trip_distance) as avg_value FROM nyc_trips GROUP BY bucket ORDER BY bucket;".execute_result(&mut conn) {
+    match "SELECT time_bucket(INTERVAL '2 DAY', tpep_pickup_datetime::TIMESTAMP, 'Europe/Berlin') AS bucket, AVG(trip_distance) as avg_value FROM nyc_trips GROUP BY bucket ORDER BY bucket;".execute_result(&mut conn) {
         Ok(_) => {
-            panic!("Should have error'ed when calling time_bucket() on non-FDW data.")
+            panic!("Should have error'ed when calling timezone-aware time_bucket() on non-FDW data.")
         }
         Err(error) => {
             let a = error.to_string().contains(error_message);
@@ -162,3 +248,48 @@ async fn test_time_bucket_fallback(mut conn: PgConnection) -> Result<()> {
 
     Ok(())
 }
+
+#[rstest]
+async fn test_time_bucket_timezone_duckdb(mut conn: PgConnection, tempdir: TempDir) -> Result<()> {
+    let stored_batch = time_series_record_batch_dst()?;
+    let parquet_path = tempdir.path().join("test_dst.parquet");
+    let parquet_file = File::create(&parquet_path)?;
+
+    let mut writer = ArrowWriter::try_new(parquet_file, stored_batch.schema(), None).unwrap();
+    writer.write(&stored_batch)?;
+    writer.close()?;
+
+    primitive_setup_fdw_local_file_listing(parquet_path.as_path().to_str().unwrap(), "MyTable")
+        .execute(&mut conn);
+
+    format!(
+        "CREATE FOREIGN TABLE timeseries () SERVER parquet_server OPTIONS (files '{}')",
+        parquet_path.to_str().unwrap()
+    )
+    .execute(&mut conn);
+
+    // Bucketing the UTC instant directly splits the range into exactly 2
+    // calendar-day buckets, since the range spans midnight UTC once.
+    let utc_data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 DAY', timestamp::TIMESTAMP) AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    // Bucketing in `Europe/Berlin` local time crosses a DST boundary inside
+    // the range (2024-03-31 is 23 hours long there), so the bucket
+    // boundaries - and therefore the bucket count - differ from the naive
+    // UTC bucketing above.
+    let berlin_data: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 DAY', timestamp::TIMESTAMP, 'Europe/Berlin') AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert_ne!(
+        utc_data.len(),
+        berlin_data.len(),
+        "a DST boundary inside the range should change the bucket count when bucketing in local time"
+    );
+
+    let with_origin: Vec<(NaiveDateTime,)> = "SELECT time_bucket(INTERVAL '1 MONTH', timestamp::TIMESTAMP, origin => TIMESTAMP '2000-01-01', timezone => 'UTC') AS bucket, AVG(value) as avg_value FROM timeseries GROUP BY bucket ORDER BY bucket;"
+        .fetch_result(&mut conn).unwrap();
+
+    assert!(!with_origin.is_empty());
+
+    Ok(())
+}